@@ -1,9 +1,14 @@
 //! Process management syscalls
+use alloc::sync::Arc;
+
 use crate::{
     config::{MAX_SYSCALL_NUM, PAGE_SIZE},
-    mm::{translated_byte_buffer, MapPermission},
+    loader::get_app_data_by_name,
+    mm::{translated_byte_buffer, translated_str, MapPermission},
     task::{
-        change_program_brk, current_user_token, exit_current_and_run_next, get_start_time, get_syscall_times, get_task_status, mmap, munmap, suspend_current_and_run_next, TaskStatus
+        add_task, change_program_brk, current_task, current_user_token,
+        exit_current_and_run_next, get_start_time, get_syscall_times, get_task_status, mmap,
+        munmap, set_priority, suspend_current_and_run_next, TaskStatus,
     },
     timer::{get_time_ms, get_time_us},
 };
@@ -126,6 +131,85 @@ pub fn sys_munmap(start: usize, len: usize) -> isize {
     -1
 
 }
+/// Set the calling task's stride-scheduling priority.
+/// Rejects `prio < 2`, since `BIG_STRIDE / priority` must not blow up the pass size.
+pub fn sys_set_priority(prio: isize) -> isize {
+    trace!("kernel: sys_set_priority");
+    if prio < 2 {
+        return -1;
+    }
+    set_priority(prio as usize);
+    prio
+}
+
+/// Clone the calling task into a new child, which is enqueued to run independently.
+/// Returns the child's pid to the parent and 0 to the child.
+pub fn sys_fork() -> isize {
+    trace!("kernel: sys_fork");
+    let current_task = current_task().unwrap();
+    let new_task = current_task.fork();
+    let new_pid = new_task.getpid();
+    // modify trap context of new_task, because it returns immediately after forking
+    let trap_cx = new_task.inner_exclusive_access().get_trap_cx();
+    // for child process, fork returns 0
+    trap_cx.x[10] = 0;
+    if add_task(new_task).is_err() {
+        // ready queue is full (only reachable with the lockfree_queue feature); the child is
+        // dropped along with its address space, same as any other failed fork.
+        return -1;
+    }
+    new_pid as isize
+}
+
+/// Replace the calling task's address space with the named application.
+pub fn sys_exec(path: *const u8) -> isize {
+    trace!("kernel: sys_exec");
+    let token = current_user_token();
+    let path = translated_str(token, path);
+    if let Some(data) = get_app_data_by_name(path.as_str()) {
+        let task = current_task().unwrap();
+        task.exec(data);
+        0
+    } else {
+        -1
+    }
+}
+
+/// Wait for a child matching `pid` (or any child when `pid == -1`) to become a zombie,
+/// reap it and report its exit code through `exit_code_ptr`.
+/// Returns the child's pid, -1 if no matching child exists, or -2 if one exists but
+/// hasn't exited yet.
+pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
+    trace!("kernel: sys_waitpid");
+    let task = current_task().unwrap();
+
+    let mut inner = task.inner_exclusive_access();
+    if !inner
+        .children
+        .iter()
+        .any(|p| pid == -1 || pid as usize == p.getpid())
+    {
+        return -1;
+    }
+    let pair = inner.children.iter().enumerate().find(|(_, p)| {
+        p.inner_exclusive_access().is_zombie() && (pid == -1 || pid as usize == p.getpid())
+    });
+    if let Some((idx, _)) = pair {
+        let child = inner.children.remove(idx);
+        // confirm that child will be deallocated after being removed from children list
+        assert_eq!(Arc::strong_count(&child), 1);
+        let found_pid = child.getpid();
+        let exit_code = child.inner_exclusive_access().exit_code;
+        // copyout() calls current_user_token(), which re-borrows this task's inner via
+        // inner_exclusive_access(); drop our borrow first or it double-borrows and panics.
+        drop(inner);
+        copyout(&exit_code, exit_code_ptr);
+        found_pid as isize
+    } else {
+        -2
+    }
+}
+
 /// change data segment size
 pub fn sys_sbrk(size: i32) -> isize {
     trace!("kernel: sys_sbrk");
@@ -0,0 +1,119 @@
+//!A cooperative async executor for kernel-side `Future`s, run alongside the preemptive scheduler
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use core::future::Future;
+use core::pin::Pin;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+use crate::sync::UPSafeCell;
+use lazy_static::*;
+
+/// Uniquely identifies an async kernel task so its waker can find it again.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct TaskId(usize);
+
+impl TaskId {
+    fn next() -> Self {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// An async kernel task: a boxed, pinned future plus the id its waker re-enqueues on wake.
+pub struct Task {
+    id: TaskId,
+    future: Pin<Box<dyn Future<Output = ()> + 'static>>,
+}
+
+impl Task {
+    fn new(future: impl Future<Output = ()> + 'static) -> Self {
+        Self {
+            id: TaskId::next(),
+            future: Box::pin(future),
+        }
+    }
+
+    fn poll(&mut self, cx: &mut Context) -> Poll<()> {
+        self.future.as_mut().poll(cx)
+    }
+}
+
+lazy_static! {
+    /// Tasks ready to be polled, parallel to the preemptive scheduler's `ready_queue`.
+    static ref READY_TASKS: UPSafeCell<VecDeque<Task>> = unsafe { UPSafeCell::new(VecDeque::new()) };
+    /// Tasks that returned `Pending` and are waiting on their waker to fire.
+    static ref PARKED_TASKS: UPSafeCell<BTreeMap<TaskId, Task>> =
+        unsafe { UPSafeCell::new(BTreeMap::new()) };
+    /// Ids whose waker fired before `Executor::run` had parked them yet, i.e. the wake raced
+    /// with the poll that produced it. Checked right after a task returns `Pending`, so this
+    /// kind of wake isn't lost.
+    static ref WOKEN_EARLY: UPSafeCell<BTreeSet<TaskId>> = unsafe { UPSafeCell::new(BTreeSet::new()) };
+}
+
+/// Enqueue a future to be cooperatively polled by [`Executor::run`].
+///
+/// I/O-bound kernel work (timers, future device drivers) can be written as `.await` chains
+/// here instead of busy-polling, while CPU-bound user processes keep using the stride
+/// scheduler in [`super::manager::TaskManager::fetch`].
+pub fn spawn(future: impl Future<Output = ()> + 'static) {
+    READY_TASKS.exclusive_access().push_back(Task::new(future));
+}
+
+/// Called from a task's waker: move it from `PARKED_TASKS` back onto `READY_TASKS`, or, if it
+/// hasn't been parked yet (the wake raced with the poll that's still holding it), just record
+/// the id so `Executor::run` can re-queue it itself once that poll returns.
+fn wake_task_id(id: TaskId) {
+    if let Some(task) = PARKED_TASKS.exclusive_access().remove(&id) {
+        READY_TASKS.exclusive_access().push_back(task);
+    } else {
+        WOKEN_EARLY.exclusive_access().insert(id);
+    }
+}
+
+fn raw_waker(id: TaskId) -> RawWaker {
+    fn clone(ptr: *const ()) -> RawWaker {
+        raw_waker(TaskId(ptr as usize))
+    }
+    fn wake(ptr: *const ()) {
+        wake_task_id(TaskId(ptr as usize));
+    }
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(clone, wake, wake, |_| {});
+    RawWaker::new(id.0 as *const (), &VTABLE)
+}
+
+fn waker_for(id: TaskId) -> Waker {
+    // SAFETY: `raw_waker`'s vtable only ever stores/reads back a `TaskId`, never dereferences
+    // the pointer, so sharing it across the wake/clone calls above is sound.
+    unsafe { Waker::from_raw(raw_waker(id)) }
+}
+
+/// The cooperative executor: pops one ready task at a time, builds a `Context` from its
+/// waker, and calls `future.as_mut().poll(cx)`, dropping tasks that complete and parking
+/// (or immediately re-queuing, if already rewoken) ones that are still pending.
+pub struct Executor;
+
+impl Executor {
+    /// Run until there is no ready async task left to poll.
+    pub fn run() {
+        loop {
+            // Pop inside its own scope so the `UPSafeCell` guard is dropped before polling:
+            // `task.poll` can `spawn()` or wake an already-parked sibling, both of which
+            // re-enter `READY_TASKS`/`PARKED_TASKS`, and the re-queue below re-enters
+            // `READY_TASKS` too.
+            let next = { READY_TASKS.exclusive_access().pop_front() };
+            let Some(mut task) = next else {
+                break;
+            };
+            let waker = waker_for(task.id);
+            let mut cx = Context::from_waker(&waker);
+            if task.poll(&mut cx) == Poll::Pending {
+                if WOKEN_EARLY.exclusive_access().remove(&task.id) {
+                    READY_TASKS.exclusive_access().push_back(task);
+                } else {
+                    PARKED_TASKS.exclusive_access().insert(task.id, task);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//!A bounded lock-free MPMC queue, for the `lockfree_queue` scheduler feature
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use super::TaskControlBlock;
+
+struct Slot<T> {
+    /// Sequence number: readable once it equals the head cursor plus one, writable once it
+    /// equals the tail cursor. This is the core of Dmitry Vyukov's bounded MPMC algorithm.
+    sequence: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+/// A fixed-capacity, contention-free MPMC ring buffer.
+///
+/// Ported to `no_std`/`alloc` in the style of `crossbeam-queue`'s `ArrayQueue`: `push`
+/// performs a CAS on the tail sequence, `pop` a CAS on the head sequence, and producers never
+/// block consumers (or each other) the way a `UPSafeCell`-guarded `VecDeque` would.
+pub struct ArrayQueue<T> {
+    buffer: Vec<Slot<T>>,
+    mask: usize,
+    head: AtomicUsize,
+    tail: AtomicUsize,
+}
+
+// SAFETY: access to each slot is synchronized by its sequence number, as in the
+// Michael-Scott / Vyukov bounded-MPMC algorithm this is ported from.
+unsafe impl<T: Send> Send for ArrayQueue<T> {}
+unsafe impl<T: Send> Sync for ArrayQueue<T> {}
+
+impl<T> ArrayQueue<T> {
+    /// Create a queue with room for `capacity` elements. `capacity` is rounded up to the
+    /// next power of two so that slot indices can be found with a bitmask.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.next_power_of_two().max(2);
+        let buffer = (0..capacity)
+            .map(|i| Slot {
+                sequence: AtomicUsize::new(i),
+                value: UnsafeCell::new(MaybeUninit::uninit()),
+            })
+            .collect();
+        Self {
+            buffer,
+            mask: capacity - 1,
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+        }
+    }
+
+    /// Try to enqueue `value`. Fails (returning it back) if the queue is full.
+    pub fn push(&self, value: T) -> Result<(), T> {
+        let mut tail = self.tail.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.buffer[tail & self.mask];
+            let seq = slot.sequence.load(Ordering::Acquire);
+            let diff = seq as isize - tail as isize;
+            if diff == 0 {
+                match self.tail.compare_exchange_weak(
+                    tail,
+                    tail + 1,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        unsafe { (*slot.value.get()).write(value) };
+                        slot.sequence.store(tail + 1, Ordering::Release);
+                        return Ok(());
+                    }
+                    Err(cur) => tail = cur,
+                }
+            } else if diff < 0 {
+                return Err(value);
+            } else {
+                tail = self.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Try to dequeue the oldest element. Returns `None` if the queue is empty.
+    pub fn pop(&self) -> Option<T> {
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            let slot = &self.buffer[head & self.mask];
+            let seq = slot.sequence.load(Ordering::Acquire);
+            let diff = seq as isize - (head + 1) as isize;
+            if diff == 0 {
+                match self.head.compare_exchange_weak(
+                    head,
+                    head + 1,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        let value = unsafe { (*slot.value.get()).assume_init_read() };
+                        slot.sequence.store(head + self.mask + 1, Ordering::Release);
+                        return Some(value);
+                    }
+                    Err(cur) => head = cur,
+                }
+            } else if diff < 0 {
+                return None;
+            } else {
+                head = self.head.load(Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// The capacity of the lock-free ready queue. Must comfortably exceed the app count.
+const LOCKFREE_QUEUE_CAPACITY: usize = 256;
+
+/// A ready-task store backed by [`ArrayQueue`] instead of a `VecDeque` behind `UPSafeCell`.
+/// Its [`LockfreeScheduler::push`]/[`LockfreeScheduler::pop`] take `&self`, so
+/// `task::manager::add_task`/`fetch_task` call them directly against a plain `lazy_static`
+/// when the `lockfree_queue` feature is enabled, with no `TaskManager`/`UPSafeCell` in the
+/// way — that's what makes concurrent harts genuinely contention-free, rather than just
+/// swapping which data structure sits behind the same single lock. The [`super::scheduler::Scheduler`]
+/// impl below exists so `LockfreeScheduler` can still be plugged into `TaskManager<S>` for
+/// comparison/testing, but it is not the path the `lockfree_queue` feature actually runs.
+///
+/// This only gives FIFO-ish ordering: there is no cheap way to find the global-minimum stride
+/// in a lock-free ring buffer, so picking this feature trades strict stride fairness for
+/// contention-free scheduling. Keep the stride `TaskManager` as the default; opt into this one
+/// with `--features lockfree_queue` for workloads that scale across many harts instead.
+pub struct LockfreeScheduler {
+    queue: ArrayQueue<Arc<TaskControlBlock>>,
+}
+
+impl Default for LockfreeScheduler {
+    fn default() -> Self {
+        Self {
+            queue: ArrayQueue::new(LOCKFREE_QUEUE_CAPACITY),
+        }
+    }
+}
+
+impl LockfreeScheduler {
+    /// Lock-free enqueue straight onto the ring buffer: a CAS on the tail sequence, no
+    /// `UPSafeCell`/`RefCell` borrow involved. This is what `task::manager::add_task` calls
+    /// directly when the `lockfree_queue` feature is enabled, bypassing `TaskManager` so that
+    /// concurrent harts genuinely don't serialize on each other.
+    pub fn push(&self, task: Arc<TaskControlBlock>) -> Result<(), Arc<TaskControlBlock>> {
+        self.queue.push(task)
+    }
+
+    /// Lock-free dequeue: a CAS on the head sequence.
+    pub fn pop(&self) -> Option<Arc<TaskControlBlock>> {
+        self.queue.pop()
+    }
+}
+
+impl super::scheduler::Scheduler for LockfreeScheduler {
+    fn insert(&mut self, task: Arc<TaskControlBlock>) -> Result<(), Arc<TaskControlBlock>> {
+        self.push(task)
+    }
+
+    fn peek(&self) -> Option<&Arc<TaskControlBlock>> {
+        // The ring buffer doesn't support peeking without removal; callers that need this
+        // (e.g. priority inspection) should prefer the stride scheduler instead.
+        None
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut Arc<TaskControlBlock>> {
+        None
+    }
+
+    fn pop(&mut self) -> Option<Arc<TaskControlBlock>> {
+        self.pop()
+    }
+
+    fn remove(&mut self, task: &Arc<TaskControlBlock>) {
+        // No O(1) removal from the middle of a lock-free ring buffer; drain and reinsert
+        // the rest. Rare enough (task kill while still queued) that this is acceptable.
+        let mut spare = Vec::new();
+        while let Some(candidate) = self.queue.pop() {
+            if !Arc::ptr_eq(&candidate, task) {
+                spare.push(candidate);
+            }
+        }
+        for candidate in spare {
+            let _ = self.queue.push(candidate);
+        }
+    }
+}
@@ -1,66 +1,106 @@
 //!Implementation of [`TaskManager`]
+use super::scheduler::{ConfiguredScheduler, Scheduler};
 use super::TaskControlBlock;
-use crate::{sync::UPSafeCell, timer::get_time_ms};
-use alloc::collections::VecDeque;
+use crate::timer::get_time_ms;
 use alloc::sync::Arc;
-use lazy_static::*;
-///A array of `TaskControlBlock` that is thread-safe
-pub struct TaskManager {
+
+///A pool of ready-to-run tasks, thread-safe, backed by a pluggable [`Scheduler`] policy
+pub struct TaskManager<S: Scheduler = ConfiguredScheduler> {
     start_time: usize,
-    ready_queue: VecDeque<Arc<TaskControlBlock>>,
+    scheduler: S,
 }
 
-/// A simple FIFO scheduler.
-impl TaskManager {
+impl<S: Scheduler + Default> TaskManager<S> {
     ///Creat an empty TaskManager
     pub fn new() -> Self {
         Self {
             start_time: get_time_ms(),
-            ready_queue: VecDeque::new(),
+            scheduler: S::default(),
         }
     }
-    /// Add process back to ready queue
-    pub fn add(&mut self, task: Arc<TaskControlBlock>) {
-        self.ready_queue.push_back(task);
+    /// Add process back to ready queue. Fails (handing the task back) if the configured
+    /// scheduler's backing storage is full.
+    pub fn add(&mut self, task: Arc<TaskControlBlock>) -> Result<(), Arc<TaskControlBlock>> {
+        self.scheduler.insert(task)
     }
     /// Take a process out of the ready queue
     pub fn fetch(&mut self) -> Option<Arc<TaskControlBlock>> {
-        // self.ready_queue.pop_front()
-        let min_index = self
-            .ready_queue
-            .iter()
-            .enumerate()
-            .min_by_key(|&(_, value)| {
-                value.inner_exclusive_access().stride
-            })
-            .map(|(index, tcb)| {
-                tcb.inner_exclusive_access().add_stride();
-                index
-            })?;
-        // println!("kernel: schedu task {}", min_index);
-        self.ready_queue.remove(min_index)
+        self.scheduler.pop()
     }
 }
 
-lazy_static! {
-    /// TASK_MANAGER instance through lazy_static!
-    pub static ref TASK_MANAGER: UPSafeCell<TaskManager> =
-        unsafe { UPSafeCell::new(TaskManager::new()) };
-}
+// The `lockfree_queue` feature exists to remove ready-queue contention between harts. Routing
+// it through `TaskManager` behind a `UPSafeCell` (a `RefCell::borrow_mut` under the hood) would
+// serialize every `add_task`/`fetch_task` again and defeat the point, so it gets its own path
+// below, straight onto `LockfreeScheduler`'s lock-free `&self` push/pop, with no manager and no
+// cell in between. Everything else keeps going through the stride/FIFO `TaskManager`.
+#[cfg(not(feature = "lockfree_queue"))]
+mod locked {
+    use super::{Scheduler, TaskControlBlock, TaskManager};
+    use crate::sync::UPSafeCell;
+    use alloc::sync::Arc;
+    use lazy_static::*;
 
-/// Add process to ready queue
-pub fn add_task(task: Arc<TaskControlBlock>) {
-    //trace!("kernel: TaskManager::add_task");
-    TASK_MANAGER.exclusive_access().add(task);
-}
+    lazy_static! {
+        /// TASK_MANAGER instance through lazy_static!
+        pub static ref TASK_MANAGER: UPSafeCell<TaskManager> =
+            unsafe { UPSafeCell::new(TaskManager::new()) };
+    }
+
+    /// Add process to ready queue. Fails (handing the task back) if the configured scheduler's
+    /// backing storage is full.
+    #[must_use]
+    pub fn add_task(task: Arc<TaskControlBlock>) -> Result<(), Arc<TaskControlBlock>> {
+        //trace!("kernel: TaskManager::add_task");
+        TASK_MANAGER.exclusive_access().add(task)
+    }
 
-/// Take a process out of the ready queue
-pub fn fetch_task() -> Option<Arc<TaskControlBlock>> {
-    //trace!("kernel: TaskManager::fetch_task");
-    TASK_MANAGER.exclusive_access().fetch()
+    /// Take a process out of the ready queue
+    pub fn fetch_task() -> Option<Arc<TaskControlBlock>> {
+        //trace!("kernel: TaskManager::fetch_task");
+        TASK_MANAGER.exclusive_access().fetch()
+    }
+
+    /// Get time stamp when the kernel starts
+    pub fn get_start_time() -> usize {
+        TASK_MANAGER.exclusive_access().start_time
+    }
 }
 
-/// Get time stamp when the kernel starts
-pub fn get_start_time() -> usize {
-    TASK_MANAGER.exclusive_access().start_time
+#[cfg(feature = "lockfree_queue")]
+mod lockfree {
+    use super::TaskControlBlock;
+    use crate::timer::get_time_ms;
+    use alloc::sync::Arc;
+    use lazy_static::*;
+
+    lazy_static! {
+        /// The lock-free ready queue itself, not wrapped in `UPSafeCell`: its `push`/`pop`
+        /// already take `&self` and synchronize purely through atomics.
+        static ref LOCKFREE_TASKS: super::super::lockfree_queue::LockfreeScheduler =
+            super::super::lockfree_queue::LockfreeScheduler::default();
+        /// Written once at boot; reading it never contends with `add_task`/`fetch_task`.
+        static ref START_TIME: usize = get_time_ms();
+    }
+
+    /// Add process to the lock-free ready queue. Fails (handing the task back) if it's full.
+    #[must_use]
+    pub fn add_task(task: Arc<TaskControlBlock>) -> Result<(), Arc<TaskControlBlock>> {
+        LOCKFREE_TASKS.push(task)
+    }
+
+    /// Take a process out of the lock-free ready queue.
+    pub fn fetch_task() -> Option<Arc<TaskControlBlock>> {
+        LOCKFREE_TASKS.pop()
+    }
+
+    /// Get time stamp when the kernel starts
+    pub fn get_start_time() -> usize {
+        *START_TIME
+    }
 }
+
+#[cfg(not(feature = "lockfree_queue"))]
+pub use locked::{add_task, fetch_task, get_start_time, TASK_MANAGER};
+#[cfg(feature = "lockfree_queue")]
+pub use lockfree::{add_task, fetch_task, get_start_time};
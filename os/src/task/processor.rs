@@ -0,0 +1,99 @@
+//!Implementation of [`Processor`] and the per-hart idle control flow
+use super::manager::fetch_task;
+use super::{TaskContext, TaskControlBlock, TaskStatus};
+use crate::sync::UPSafeCell;
+use alloc::sync::Arc;
+use lazy_static::*;
+
+/// The maximum number of harts this kernel is built to schedule across.
+const MAX_HARTS: usize = 8;
+
+/// Per-hart scheduling context: the task currently running on this hart,
+/// plus the idle control flow `schedule` switches back into when it yields
+/// or exits. Splitting this out of [`super::manager::TaskManager`] keeps
+/// "who is running on this hart" separate from "what is runnable", which is
+/// what lets several harts pull from the shared ready queue independently.
+pub struct Processor {
+    current: Option<Arc<TaskControlBlock>>,
+    idle_task_cx: TaskContext,
+}
+
+impl Processor {
+    ///Create an empty Processor
+    pub fn new() -> Self {
+        Self {
+            current: None,
+            idle_task_cx: TaskContext::zero_init(),
+        }
+    }
+    fn get_idle_task_cx_ptr(&mut self) -> *mut TaskContext {
+        &mut self.idle_task_cx as *mut _
+    }
+    /// Take the task currently assigned to this hart, leaving it empty.
+    pub fn take_current(&mut self) -> Option<Arc<TaskControlBlock>> {
+        self.current.take()
+    }
+    /// Clone a handle to the task currently assigned to this hart.
+    pub fn current(&self) -> Option<Arc<TaskControlBlock>> {
+        self.current.as_ref().map(Arc::clone)
+    }
+}
+
+lazy_static! {
+    /// One [`Processor`] per hart, indexed by hart id, each behind its own [`UPSafeCell`] so
+    /// that harts scheduling concurrently borrow independent cells instead of contending (and
+    /// panicking on a `borrow_mut` conflict) over a single cell for the whole array.
+    pub static ref PROCESSORS: [UPSafeCell<Processor>; MAX_HARTS] =
+        core::array::from_fn(|_| unsafe { UPSafeCell::new(Processor::new()) });
+}
+
+/// The id of the hart executing this code, stashed in `tp` at boot by each hart's entry stub.
+pub fn current_hartid() -> usize {
+    let hartid: usize;
+    unsafe {
+        core::arch::asm!("mv {0}, tp", out(reg) hartid);
+    }
+    hartid
+}
+
+/// This hart's idle loop: repeatedly fetch the next ready task from the
+/// shared [`super::manager::TaskManager`] and switch into it, returning here
+/// once that task yields or exits.
+pub fn run_tasks() {
+    let hartid = current_hartid();
+    loop {
+        if let Some(task) = fetch_task() {
+            let mut processor = PROCESSORS[hartid].exclusive_access();
+            let idle_task_cx_ptr = processor.get_idle_task_cx_ptr();
+            let next_task_cx_ptr = {
+                let mut task_inner = task.inner_exclusive_access();
+                task_inner.task_status = TaskStatus::Running;
+                &task_inner.task_cx as *const TaskContext
+            };
+            processor.current = Some(task);
+            drop(processor);
+            unsafe {
+                super::switch::__switch(idle_task_cx_ptr, next_task_cx_ptr);
+            }
+        }
+    }
+}
+
+/// The task running on the current hart, if any.
+pub fn current_task() -> Option<Arc<TaskControlBlock>> {
+    PROCESSORS[current_hartid()].exclusive_access().current()
+}
+
+/// Take the task running on the current hart out of its [`Processor`].
+pub fn take_current_task() -> Option<Arc<TaskControlBlock>> {
+    PROCESSORS[current_hartid()].exclusive_access().take_current()
+}
+
+/// Switch out of a task's control flow back into the current hart's idle loop.
+pub fn schedule(switched_task_cx_ptr: *mut TaskContext) {
+    let hartid = current_hartid();
+    let idle_task_cx_ptr = PROCESSORS[hartid].exclusive_access().get_idle_task_cx_ptr();
+    unsafe {
+        super::switch::__switch(switched_task_cx_ptr, idle_task_cx_ptr);
+    }
+}
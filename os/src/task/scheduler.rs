@@ -0,0 +1,150 @@
+//!Pluggable scheduling policies used by [`super::manager::TaskManager`]
+use super::TaskControlBlock;
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+
+/// A scheduling policy that owns the ready-task storage and decides which
+/// ready task runs next.
+///
+/// Pulling this out of [`super::manager::TaskManager`] lets the manager stay
+/// a thin wrapper around whichever policy is configured, so FIFO, stride and
+/// future round-robin policies can be compared without touching the manager
+/// itself.
+pub trait Scheduler {
+    /// Insert a task that has become ready to run. Fails (handing the task back) if the
+    /// policy's backing storage is full, e.g. a fixed-capacity lock-free ring buffer.
+    fn insert(&mut self, task: Arc<TaskControlBlock>) -> Result<(), Arc<TaskControlBlock>>;
+    /// Look at the task that [`Scheduler::pop`] would return, without removing it.
+    /// A policy whose storage cannot be inspected without removal returns `None` here even
+    /// when tasks are queued.
+    fn peek(&self) -> Option<&Arc<TaskControlBlock>>;
+    /// Mutable version of [`Scheduler::peek`].
+    fn peek_mut(&mut self) -> Option<&mut Arc<TaskControlBlock>>;
+    /// Remove and return the next task to run, according to the policy.
+    fn pop(&mut self) -> Option<Arc<TaskControlBlock>>;
+    /// Remove a specific task from the ready set, e.g. when it is killed while still queued.
+    fn remove(&mut self, task: &Arc<TaskControlBlock>);
+}
+
+/// First-in-first-out scheduler: tasks run in the order they became ready.
+#[derive(Default)]
+pub struct FifoScheduler {
+    queue: VecDeque<Arc<TaskControlBlock>>,
+}
+
+impl Scheduler for FifoScheduler {
+    fn insert(&mut self, task: Arc<TaskControlBlock>) -> Result<(), Arc<TaskControlBlock>> {
+        self.queue.push_back(task);
+        Ok(())
+    }
+
+    fn peek(&self) -> Option<&Arc<TaskControlBlock>> {
+        self.queue.front()
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut Arc<TaskControlBlock>> {
+        self.queue.front_mut()
+    }
+
+    fn pop(&mut self) -> Option<Arc<TaskControlBlock>> {
+        self.queue.pop_front()
+    }
+
+    fn remove(&mut self, task: &Arc<TaskControlBlock>) {
+        if let Some(pos) = self.queue.iter().position(|t| Arc::ptr_eq(t, task)) {
+            self.queue.remove(pos);
+        }
+    }
+}
+
+/// Stride used to compute each task's per-schedule increment: `pass = BIG_STRIDE / priority`.
+/// Kept large so that `pass <= BIG_STRIDE / 2` whenever `priority >= 2`, which is what keeps
+/// `stride_less` correct across `stride` overflow (see its doc comment).
+///
+/// The `pass`/`add_stride` computation itself lives on the TCB, outside this file (and outside
+/// this tree, which has no TCB source under `task/`), so nothing here
+/// calls this constant — hence the `allow(dead_code)`. That also means this crate cannot
+/// verify that the TCB's `add_stride` actually divides by *this* `BIG_STRIDE` rather than a
+/// constant of its own; if it does define its own, the two will drift and the `stride_less`
+/// safe-window proof (`pass <= BIG_STRIDE / 2`) stops holding. Whoever wires up the TCB side
+/// must import this constant rather than redefine it.
+#[allow(dead_code)]
+pub const BIG_STRIDE: u64 = u64::MAX;
+
+/// Compare two `stride` values the way the stride scheduler needs to: tolerant of the
+/// wraparound that happens once a task's `stride` overflows `u64`.
+///
+/// `a.wrapping_sub(b)` is reinterpreted as a signed 64-bit delta; `a` is "less than" `b` when
+/// that delta is negative. This matches plain `a < b` as long as the two strides are within
+/// `BIG_STRIDE / 2` of each other (the reinterpret-as-`i64` trick is only unambiguous within
+/// half the `u64` range), which holds here because every `pass` is at most `BIG_STRIDE / 2`.
+fn stride_less(a: u64, b: u64) -> bool {
+    (a.wrapping_sub(b) as i64) < 0
+}
+
+/// Stride scheduler: always runs the ready task with the smallest `stride`,
+/// advancing the winner's `stride` by its own `pass` each time it is picked.
+#[derive(Default)]
+pub struct StrideScheduler {
+    queue: VecDeque<Arc<TaskControlBlock>>,
+}
+
+impl StrideScheduler {
+    fn min_index(&self) -> Option<usize> {
+        let mut min_index = None;
+        for (index, task) in self.queue.iter().enumerate() {
+            let stride = task.inner_exclusive_access().stride;
+            min_index = match min_index {
+                None => Some(index),
+                Some(best) => {
+                    let best_stride = self.queue[best].inner_exclusive_access().stride;
+                    if stride_less(stride, best_stride) {
+                        Some(index)
+                    } else {
+                        Some(best)
+                    }
+                }
+            };
+        }
+        min_index
+    }
+}
+
+impl Scheduler for StrideScheduler {
+    fn insert(&mut self, task: Arc<TaskControlBlock>) -> Result<(), Arc<TaskControlBlock>> {
+        self.queue.push_back(task);
+        Ok(())
+    }
+
+    fn peek(&self) -> Option<&Arc<TaskControlBlock>> {
+        self.min_index().map(|index| &self.queue[index])
+    }
+
+    fn peek_mut(&mut self) -> Option<&mut Arc<TaskControlBlock>> {
+        self.min_index().map(move |index| &mut self.queue[index])
+    }
+
+    fn pop(&mut self) -> Option<Arc<TaskControlBlock>> {
+        let index = self.min_index()?;
+        let task = self.queue.remove(index)?;
+        task.inner_exclusive_access().add_stride();
+        Some(task)
+    }
+
+    fn remove(&mut self, task: &Arc<TaskControlBlock>) {
+        if let Some(pos) = self.queue.iter().position(|t| Arc::ptr_eq(t, task)) {
+            self.queue.remove(pos);
+        }
+    }
+}
+
+// The scheduling policy backing `TaskManager` by default. Select a different one at build
+// time, e.g. `--features fifo_scheduler` or `--features lockfree_queue`. `lockfree_queue`
+// takes priority over `fifo_scheduler` since it replaces the underlying storage rather than
+// just the ordering; see `LockfreeScheduler`'s doc comment for the fairness it gives up.
+#[cfg(feature = "lockfree_queue")]
+pub type ConfiguredScheduler = super::lockfree_queue::LockfreeScheduler;
+#[cfg(all(feature = "fifo_scheduler", not(feature = "lockfree_queue")))]
+pub type ConfiguredScheduler = FifoScheduler;
+#[cfg(not(any(feature = "fifo_scheduler", feature = "lockfree_queue")))]
+pub type ConfiguredScheduler = StrideScheduler;